@@ -0,0 +1,235 @@
+//! Click and force-touch detection built on the pressure fields the
+//! hardware already reports, plus the framework's raw button path
+//! callback — see the Linux `hid-magicmouse` driver notes on how the
+//! Magic Trackpad 2 reports click state alongside pressure.
+
+use crate::{DeviceType, MTDevice, MTDeviceRef, MTTouch};
+use anyhow::{bail, Result};
+use std::{
+    ffi::c_void,
+    panic::catch_unwind,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+#[link(name = "MultitouchSupport", kind = "framework")]
+extern "C" {
+    /// Delivers raw path/button updates. Underdocumented outside of the
+    /// framework itself; empirically `button_state` is 0 or 1 for a
+    /// physical click.
+    fn MTRegisterPathCallback(_: MTDeviceRef, _: MTPathCallbackFunction, extra: *mut c_void);
+}
+
+type MTPathCallbackFunction = extern "C" fn(MTDeviceRef, i32, i32, *mut c_void) -> i32;
+
+/// The physical button state for a frame, as surfaced by
+/// [`MTDevice::listen_with_clicks`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ClickState {
+    pub clicked: bool,
+}
+
+/// A coarse pressure level derived from [`MTTouch::z_total`] /
+/// [`MTTouch::z_density`], with hysteresis applied so a touch hovering
+/// right at a boundary doesn't chatter between levels.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForceLevel {
+    Light,
+    Medium,
+    Force,
+}
+
+/// Pressure thresholds used to classify [`ForceLevel`]. Magic Mouse and
+/// Magic Trackpad report pressure on different scales, so calibration is
+/// keyed off [`DeviceType`].
+#[derive(Debug, Clone, Copy)]
+pub struct ForceCalibration {
+    pub medium_threshold: f32,
+    pub force_threshold: f32,
+    pub hysteresis: f32,
+}
+
+impl ForceCalibration {
+    /// A reasonable starting calibration for `device_type`. These numbers
+    /// are estimates, not measured per-model values.
+    pub fn for_device_type(device_type: DeviceType) -> Self {
+        match device_type {
+            DeviceType::MagicMouse => Self {
+                medium_threshold: 50.0,
+                force_threshold: 150.0,
+                hysteresis: 8.0,
+            },
+            _ => Self {
+                medium_threshold: 30.0,
+                force_threshold: 90.0,
+                hysteresis: 5.0,
+            },
+        }
+    }
+}
+
+impl Default for ForceCalibration {
+    fn default() -> Self {
+        Self::for_device_type(DeviceType::InternalTrackpad)
+    }
+}
+
+/// Turns per-frame pressure fields into a debounced [`ForceLevel`].
+pub struct ForceTracker {
+    calibration: ForceCalibration,
+    level: ForceLevel,
+}
+
+impl ForceTracker {
+    pub fn new(calibration: ForceCalibration) -> Self {
+        Self {
+            calibration,
+            level: ForceLevel::Light,
+        }
+    }
+
+    /// Feeds the current frame's touches in and returns the (possibly
+    /// unchanged) force level.
+    pub fn update<'a>(&mut self, touches: impl IntoIterator<Item = &'a MTTouch>) -> ForceLevel {
+        let metric = touches
+            .into_iter()
+            .map(pressure_metric)
+            .fold(0.0f32, f32::max);
+
+        let ForceCalibration {
+            medium_threshold,
+            force_threshold,
+            hysteresis,
+        } = self.calibration;
+
+        self.level = match self.level {
+            ForceLevel::Light if metric >= medium_threshold + hysteresis => {
+                if metric >= force_threshold + hysteresis {
+                    ForceLevel::Force
+                } else {
+                    ForceLevel::Medium
+                }
+            }
+            ForceLevel::Force if metric <= force_threshold - hysteresis => {
+                if metric <= medium_threshold - hysteresis {
+                    ForceLevel::Light
+                } else {
+                    ForceLevel::Medium
+                }
+            }
+            ForceLevel::Medium if metric >= force_threshold + hysteresis => ForceLevel::Force,
+            ForceLevel::Medium if metric <= medium_threshold - hysteresis => ForceLevel::Light,
+            unchanged => unchanged,
+        };
+
+        self.level
+    }
+}
+
+fn pressure_metric(touch: &MTTouch) -> f32 {
+    touch.z_total.abs() * touch.z_density.max(0.01)
+}
+
+extern "C" fn path_callback(
+    _device: MTDeviceRef,
+    _path_id: i32,
+    button_state: i32,
+    extra: *mut c_void,
+) -> i32 {
+    match catch_unwind(|| {
+        let clicked = unsafe { &*(extra as *const AtomicBool) };
+        clicked.store(button_state != 0, Ordering::SeqCst);
+    }) {
+        Ok(_) => 0,
+        Err(_) => -1,
+    }
+}
+
+impl MTDevice {
+    /// Like [`Self::listen`], but also surfaces the physical click state and
+    /// a calibrated [`ForceLevel`] alongside each frame.
+    pub fn listen_with_clicks<F>(&mut self, calibration: ForceCalibration, callback: F) -> Result<()>
+    where
+        F: Fn(&[MTTouch], ClickState, ForceLevel, i32, f64, i32) + Send + Sync + 'static,
+    {
+        if self.is_running() {
+            bail!("already listening");
+        }
+
+        let clicked = Box::into_raw(Box::new(AtomicBool::new(false)));
+        unsafe { MTRegisterPathCallback(self.inner(), path_callback, clicked as *mut c_void) };
+        // Stashed as an address (rather than the raw pointer itself) so the
+        // closure below stays Send + Sync; `clicked` is leaked intentionally
+        // and stays valid for as long as the device keeps calling back.
+        let clicked_addr = clicked as usize;
+
+        let tracker = Mutex::new(ForceTracker::new(calibration));
+
+        self.listen(move |_device, touches, fingers, timestamp, frame| {
+            let click_state = ClickState {
+                clicked: unsafe { &*(clicked_addr as *const AtomicBool) }.load(Ordering::SeqCst),
+            };
+            let force_level = tracker.lock().unwrap().update(touches);
+            callback(touches, click_state, force_level, fingers, timestamp, frame);
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn touch_with_pressure(z_total: f32, z_density: f32) -> MTTouch {
+        MTTouch {
+            z_total,
+            z_density,
+            ..crate::sample_touch()
+        }
+    }
+
+    fn test_calibration() -> ForceCalibration {
+        ForceCalibration {
+            medium_threshold: 30.0,
+            force_threshold: 90.0,
+            hysteresis: 5.0,
+        }
+    }
+
+    #[test]
+    fn stays_light_below_medium_threshold() {
+        let mut tracker = ForceTracker::new(test_calibration());
+        let touch = touch_with_pressure(10.0, 1.0);
+        assert_eq!(tracker.update([&touch]), ForceLevel::Light);
+    }
+
+    #[test]
+    fn escalates_through_medium_to_force() {
+        let mut tracker = ForceTracker::new(test_calibration());
+
+        let medium = touch_with_pressure(40.0, 1.0);
+        assert_eq!(tracker.update([&medium]), ForceLevel::Medium);
+
+        let forceful = touch_with_pressure(100.0, 1.0);
+        assert_eq!(tracker.update([&forceful]), ForceLevel::Force);
+    }
+
+    #[test]
+    fn hysteresis_prevents_chatter_right_at_the_boundary() {
+        let mut tracker = ForceTracker::new(test_calibration());
+
+        // Inside the hysteresis band above the medium threshold: not enough
+        // to escalate yet.
+        let just_above_medium = touch_with_pressure(33.0, 1.0);
+        assert_eq!(tracker.update([&just_above_medium]), ForceLevel::Light);
+
+        let solidly_medium = touch_with_pressure(40.0, 1.0);
+        assert_eq!(tracker.update([&solidly_medium]), ForceLevel::Medium);
+
+        // Inside the hysteresis band below the medium threshold: should not
+        // drop back to Light immediately.
+        let dips_back_near_medium = touch_with_pressure(27.0, 1.0);
+        assert_eq!(tracker.update([&dips_back_near_medium]), ForceLevel::Medium);
+    }
+}
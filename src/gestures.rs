@@ -0,0 +1,516 @@
+//! A higher-level gesture recognizer built on top of [`MTDevice::listen`].
+//!
+//! Instead of raw [`MTTouch`] slices, [`MTDevice::listen_gestures`] emits
+//! semantic [`GestureEvent`]s (tap, swipe, pinch, rotate), in the spirit of
+//! the gesture-translation approach in
+//! <https://github.com/JitouchApp/Jitouch/blob/3b5018e4bc839426a6ce0917cea6df753d19da10/Application/Gesture.m#L2930>.
+
+use crate::click::{ForceCalibration, ForceLevel, ForceTracker};
+use crate::{MTDevice, MTPoint, MTTouch, MTTouchState};
+use anyhow::Result;
+use std::{
+    collections::HashMap,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::{Duration, Instant},
+};
+
+/// How often the background timer checks [`GestureRecognizer`] for
+/// scheduled gestures that came due without a new frame arriving.
+const GESTURE_TIMER_TICK: Duration = Duration::from_millis(20);
+
+/// Tunable thresholds for [`GestureRecognizer`].
+#[derive(Debug, Clone)]
+pub struct GestureConfig {
+    /// A touch that lifts within this duration and under `tap_max_movement`
+    /// is recognized as a tap.
+    pub tap_max_duration: Duration,
+    /// Maximum normalized distance a tap is allowed to travel.
+    pub tap_max_movement: f32,
+    /// Minimum fractional change in mean pairwise touch distance before a
+    /// pinch is reported.
+    pub pinch_threshold: f32,
+    /// Minimum change in the mean per-touch angle around the centroid, in
+    /// radians, before a rotation is reported.
+    pub rotation_threshold: f32,
+    /// Minimum normalized velocity magnitude for a swipe to be reported.
+    pub swipe_velocity_threshold: f32,
+    /// Finger counts that are recognized as swipes, e.g. `[2, 3, 4]`.
+    pub swipe_finger_counts: Vec<usize>,
+    /// Pressure thresholds used to detect [`GestureEvent::ForceTouch`].
+    pub force_calibration: ForceCalibration,
+    /// How long a touch must stay down, without moving more than
+    /// `tap_max_movement`, before it's recognized as a [`GestureEvent::Hold`]
+    /// rather than a tap.
+    pub hold_duration: Duration,
+    /// How long a [`GestureEvent::Tap`] waits to see if a second tap follows
+    /// before it fires; a second tap in this window, within
+    /// `4 * tap_max_movement` of the first, becomes a
+    /// [`GestureEvent::DoubleTap`] instead.
+    pub double_tap_window: Duration,
+}
+
+impl Default for GestureConfig {
+    fn default() -> Self {
+        Self {
+            tap_max_duration: Duration::from_millis(200),
+            tap_max_movement: 0.02,
+            pinch_threshold: 0.08,
+            rotation_threshold: 0.09,
+            swipe_velocity_threshold: 0.15,
+            swipe_finger_counts: vec![2, 3, 4],
+            force_calibration: ForceCalibration::default(),
+            hold_duration: Duration::from_millis(500),
+            double_tap_window: Duration::from_millis(300),
+        }
+    }
+}
+
+/// A semantic gesture emitted by [`GestureRecognizer`].
+#[derive(Debug, Clone, Copy)]
+pub enum GestureEvent {
+    /// A touch appeared and disappeared within `tap_max_duration` without
+    /// moving more than `tap_max_movement`, and no second tap followed
+    /// near it within `double_tap_window`.
+    Tap { position: (f32, f32) },
+    /// A second [`GestureEvent::Tap`]-eligible touch followed the first
+    /// within `double_tap_window`, lifting within `4 * tap_max_movement` of
+    /// it; the individual taps are not also reported.
+    DoubleTap { position: (f32, f32) },
+    /// A touch stayed down for `hold_duration` without moving more than
+    /// `tap_max_movement`.
+    Hold { position: (f32, f32) },
+    /// `finger_count` touches moved together in roughly the same direction.
+    Swipe {
+        finger_count: usize,
+        direction: (f32, f32),
+    },
+    /// The mean pairwise distance between touches changed by more than
+    /// `pinch_threshold`. `scale` is `d_now / d_start`.
+    Pinch { scale: f32 },
+    /// The mean per-touch angle around the centroid changed by more than
+    /// `rotation_threshold`. `delta_angle` is in radians.
+    Rotate { delta_angle: f32 },
+    /// A touch's pressure crossed into [`ForceLevel::Force`].
+    ForceTouch { position: (f32, f32) },
+}
+
+/// The bookkeeping state this module keeps for a single active touch,
+/// keyed by [`MTTouch::identifier`].
+struct TrackedTouch {
+    start_pos: MTPoint,
+    start_instant: Instant,
+    last_pos: MTPoint,
+}
+
+/// What a [`ScheduledGesture`] will emit once it fires.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ScheduledKind {
+    Tap,
+    Hold,
+}
+
+/// A gesture waiting for its settling window to pass, modeled on
+/// InputPlumber's `ScheduledNativeEvent`: it carries a `fire_at` and is only
+/// emitted once that deadline passes, unless it's canceled first by a
+/// contradicting touch (a second tap, or movement/lift during a hold).
+struct ScheduledGesture {
+    identifier: i32,
+    kind: ScheduledKind,
+    position: (f32, f32),
+    fire_at: Instant,
+}
+
+/// Tracks active touches frame over frame and turns them into
+/// [`GestureEvent`]s. Built by [`MTDevice::listen_gestures`]; most callers
+/// won't need to construct this directly.
+pub struct GestureRecognizer {
+    config: GestureConfig,
+    touches: HashMap<i32, TrackedTouch>,
+    pinch_start_distance: Option<f32>,
+    last_mean_angle: Option<f32>,
+    force_tracker: ForceTracker,
+    force_touch_emitted: bool,
+    scheduled: Vec<ScheduledGesture>,
+}
+
+impl GestureRecognizer {
+    pub fn new(config: GestureConfig) -> Self {
+        let force_tracker = ForceTracker::new(config.force_calibration);
+        Self {
+            config,
+            touches: HashMap::new(),
+            pinch_start_distance: None,
+            last_mean_angle: None,
+            force_tracker,
+            force_touch_emitted: false,
+            scheduled: Vec::new(),
+        }
+    }
+
+    /// Drains any scheduled gestures whose `fire_at` has passed. Called both
+    /// from [`Self::process_frame`] and from the background timer thread
+    /// started by [`MTDevice::listen_gestures`], so delayed events still
+    /// fire even when touches stop arriving.
+    pub fn drain_due(&mut self, now: Instant) -> Vec<GestureEvent> {
+        let mut events = Vec::new();
+        self.scheduled.retain(|scheduled| {
+            if scheduled.fire_at > now {
+                return true;
+            }
+            events.push(match scheduled.kind {
+                ScheduledKind::Tap => GestureEvent::Tap {
+                    position: scheduled.position,
+                },
+                ScheduledKind::Hold => GestureEvent::Hold {
+                    position: scheduled.position,
+                },
+            });
+            false
+        });
+        events
+    }
+
+    /// Feeds one frame's worth of touches into the recognizer and returns
+    /// whatever gestures it produced.
+    pub fn process_frame(&mut self, touches: &[MTTouch]) -> Vec<GestureEvent> {
+        let now = Instant::now();
+        let mut events = Vec::new();
+
+        for touch in touches {
+            match touch.state {
+                MTTouchState::MakeTouch | MTTouchState::Touching => {
+                    let is_new = !self.touches.contains_key(&touch.identifier);
+                    let tracked = self.touches.entry(touch.identifier).or_insert_with(|| {
+                        TrackedTouch {
+                            start_pos: touch.normalized.pos,
+                            start_instant: now,
+                            last_pos: touch.normalized.pos,
+                        }
+                    });
+                    tracked.last_pos = touch.normalized.pos;
+
+                    if is_new {
+                        self.scheduled.push(ScheduledGesture {
+                            identifier: touch.identifier,
+                            kind: ScheduledKind::Hold,
+                            position: (touch.normalized.pos.x, touch.normalized.pos.y),
+                            fire_at: now + self.config.hold_duration,
+                        });
+                    } else if distance(tracked.start_pos, tracked.last_pos)
+                        > self.config.tap_max_movement
+                    {
+                        // Moved too far to still be a hold-in-place; drop the
+                        // pending hold so dragging doesn't also fire one.
+                        self.cancel_scheduled(touch.identifier, ScheduledKind::Hold, now);
+                    }
+                }
+                MTTouchState::BreakTouch | MTTouchState::OutOfRange => {
+                    if let Some(tracked) = self.touches.remove(&touch.identifier) {
+                        self.cancel_scheduled(touch.identifier, ScheduledKind::Hold, now);
+
+                        let duration = tracked.start_instant.elapsed();
+                        let movement = distance(tracked.start_pos, touch.normalized.pos);
+                        let position = (touch.normalized.pos.x, touch.normalized.pos.y);
+
+                        if duration <= self.config.tap_max_duration
+                            && movement <= self.config.tap_max_movement
+                        {
+                            // A double tap must land near the pending tap, not
+                            // just within its time window.
+                            let double_tap_radius = self.config.tap_max_movement * 4.0;
+                            if let Some(index) = self.scheduled.iter().position(|s| {
+                                s.kind == ScheduledKind::Tap
+                                    && s.fire_at > now
+                                    && distance(
+                                        MTPoint {
+                                            x: s.position.0,
+                                            y: s.position.1,
+                                        },
+                                        MTPoint {
+                                            x: position.0,
+                                            y: position.1,
+                                        },
+                                    ) <= double_tap_radius
+                            }) {
+                                self.scheduled.remove(index);
+                                events.push(GestureEvent::DoubleTap { position });
+                            } else {
+                                self.scheduled.push(ScheduledGesture {
+                                    identifier: touch.identifier,
+                                    kind: ScheduledKind::Tap,
+                                    position,
+                                    fire_at: now + self.config.double_tap_window,
+                                });
+                            }
+                        }
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        events.extend(self.drain_due(now));
+
+        let active: Vec<&MTTouch> = touches
+            .iter()
+            .filter(|t| matches!(t.state, MTTouchState::MakeTouch | MTTouchState::Touching))
+            .collect();
+
+        if active.is_empty() {
+            self.force_touch_emitted = false;
+        } else {
+            let level = self
+                .force_tracker
+                .update(active.iter().copied());
+            if level == ForceLevel::Force {
+                if !self.force_touch_emitted {
+                    let centroid = centroid_of(&active);
+                    events.push(GestureEvent::ForceTouch {
+                        position: (centroid.x, centroid.y),
+                    });
+                    self.force_touch_emitted = true;
+                }
+            } else {
+                self.force_touch_emitted = false;
+            }
+        }
+
+        if active.len() < 2 {
+            self.pinch_start_distance = None;
+            self.last_mean_angle = None;
+        } else {
+            let centroid = centroid_of(&active);
+
+            let (mut pair_sum, mut pairs) = (0.0, 0);
+            for (i, a) in active.iter().enumerate() {
+                for b in &active[i + 1..] {
+                    pair_sum += distance(a.normalized.pos, b.normalized.pos);
+                    pairs += 1;
+                }
+            }
+            let mean_distance = pair_sum / pairs as f32;
+
+            let pinch_start = *self.pinch_start_distance.get_or_insert(mean_distance);
+            if pinch_start > 0.0 {
+                let change = (mean_distance - pinch_start).abs() / pinch_start;
+                if change >= self.config.pinch_threshold {
+                    events.push(GestureEvent::Pinch {
+                        scale: mean_distance / pinch_start,
+                    });
+                    self.pinch_start_distance = Some(mean_distance);
+                }
+            }
+
+            let mean_angle = circular_mean(
+                active
+                    .iter()
+                    .map(|t| angle_around(centroid, t.normalized.pos)),
+            );
+            if let Some(last) = self.last_mean_angle {
+                let delta = wrap_angle(mean_angle - last);
+                if delta.abs() >= self.config.rotation_threshold {
+                    events.push(GestureEvent::Rotate { delta_angle: delta });
+                    self.last_mean_angle = Some(mean_angle);
+                }
+            } else {
+                self.last_mean_angle = Some(mean_angle);
+            }
+        }
+
+        if self.config.swipe_finger_counts.contains(&active.len()) && !active.is_empty() {
+            let mean_vel = MTPoint {
+                x: active.iter().map(|t| t.normalized.vel.x).sum::<f32>() / active.len() as f32,
+                y: active.iter().map(|t| t.normalized.vel.y).sum::<f32>() / active.len() as f32,
+            };
+            let magnitude = (mean_vel.x * mean_vel.x + mean_vel.y * mean_vel.y).sqrt();
+
+            let consistent = active.iter().all(|t| {
+                t.normalized.vel.x * mean_vel.x + t.normalized.vel.y * mean_vel.y >= 0.0
+            });
+
+            if magnitude >= self.config.swipe_velocity_threshold && consistent {
+                events.push(GestureEvent::Swipe {
+                    finger_count: active.len(),
+                    direction: (mean_vel.x, mean_vel.y),
+                });
+            }
+        }
+
+        events
+    }
+
+    /// Drops a pending scheduled gesture for `identifier`/`kind` if one
+    /// exists and hasn't come due yet, without firing it. Used when a
+    /// contradicting touch event (movement past the tap threshold, or a
+    /// lift) invalidates it. An entry whose `fire_at` has already passed is
+    /// left alone so the next `drain_due` still emits it instead of losing
+    /// it to a race with the lift/movement that arrived in the same tick.
+    fn cancel_scheduled(&mut self, identifier: i32, kind: ScheduledKind, now: Instant) {
+        self.scheduled
+            .retain(|s| !(s.identifier == identifier && s.kind == kind && s.fire_at > now));
+    }
+}
+
+fn distance(a: MTPoint, b: MTPoint) -> f32 {
+    ((a.x - b.x).powi(2) + (a.y - b.y).powi(2)).sqrt()
+}
+
+fn centroid_of(touches: &[&MTTouch]) -> MTPoint {
+    let (mut x, mut y) = (0.0, 0.0);
+    for t in touches {
+        x += t.normalized.pos.x;
+        y += t.normalized.pos.y;
+    }
+    let n = touches.len() as f32;
+    MTPoint { x: x / n, y: y / n }
+}
+
+fn angle_around(centroid: MTPoint, point: MTPoint) -> f32 {
+    (point.y - centroid.y).atan2(point.x - centroid.x)
+}
+
+/// Averages angles by their unit vectors so wraparound near ±π doesn't skew
+/// the mean.
+fn circular_mean(angles: impl Iterator<Item = f32>) -> f32 {
+    let (mut sin_sum, mut cos_sum, mut count) = (0.0, 0.0, 0);
+    for a in angles {
+        sin_sum += a.sin();
+        cos_sum += a.cos();
+        count += 1;
+    }
+    if count == 0 {
+        0.0
+    } else {
+        sin_sum.atan2(cos_sum)
+    }
+}
+
+/// Wraps an angle difference into `[-π, π]` so a jump from just under π to
+/// just over -π doesn't read as a near-full rotation.
+fn wrap_angle(delta: f32) -> f32 {
+    let two_pi = std::f32::consts::PI * 2.0;
+    let mut wrapped = delta % two_pi;
+    if wrapped > std::f32::consts::PI {
+        wrapped -= two_pi;
+    } else if wrapped < -std::f32::consts::PI {
+        wrapped += two_pi;
+    }
+    wrapped
+}
+
+/// A handle to the background timer thread started by
+/// [`MTDevice::listen_gestures`]. Dropping the handle does not stop the
+/// thread; call [`Self::stop`] to do that.
+pub struct GestureListenHandle {
+    stop: Arc<AtomicBool>,
+}
+
+impl GestureListenHandle {
+    /// Stops the background timer thread that drains scheduled gestures.
+    /// Call this before calling [`MTDevice::listen_gestures`] again on the
+    /// same or a re-identified device (e.g. after a [`crate::monitor::DeviceEvent::Added`]
+    /// reconnect), so threads don't accumulate across reconnects.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+impl MTDevice {
+    /// Like [`Self::listen`], but drives `callback` with semantic
+    /// [`GestureEvent`]s instead of raw touch frames.
+    ///
+    /// Gestures with a settling window (tap-vs-hold, double-tap) are
+    /// scheduled rather than emitted immediately; a background thread ticks
+    /// every [`GESTURE_TIMER_TICK`] so they still fire once their deadline
+    /// passes even if no further frames arrive. Call [`GestureListenHandle::stop`]
+    /// on the returned handle to stop that thread.
+    pub fn listen_gestures<F>(
+        &mut self,
+        config: GestureConfig,
+        callback: F,
+    ) -> Result<GestureListenHandle>
+    where
+        F: Fn(GestureEvent) + Send + Sync + 'static,
+    {
+        let recognizer = Arc::new(Mutex::new(GestureRecognizer::new(config)));
+        let callback = Arc::new(callback);
+        let stop = Arc::new(AtomicBool::new(false));
+
+        let recognizer_for_frames = Arc::clone(&recognizer);
+        let callback_for_frames = Arc::clone(&callback);
+        self.listen(move |_device, touches, _fingers, _timestamp, _frame| {
+            let events = recognizer_for_frames
+                .lock()
+                .unwrap()
+                .process_frame(touches);
+            for event in events {
+                callback_for_frames(event);
+            }
+        })?;
+
+        let recognizer_for_timer = Arc::clone(&recognizer);
+        let stop_for_timer = Arc::clone(&stop);
+        thread::spawn(move || {
+            while !stop_for_timer.load(Ordering::SeqCst) {
+                thread::sleep(GESTURE_TIMER_TICK);
+                let events = recognizer_for_timer.lock().unwrap().drain_due(Instant::now());
+                for event in events {
+                    callback(event);
+                }
+            }
+        });
+
+        Ok(GestureListenHandle { stop })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn distance_matches_pythagorean_theorem() {
+        let a = MTPoint { x: 0.0, y: 0.0 };
+        let b = MTPoint { x: 3.0, y: 4.0 };
+        assert!((distance(a, b) - 5.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn circular_mean_of_no_angles_is_zero() {
+        assert_eq!(circular_mean(std::iter::empty()), 0.0);
+    }
+
+    #[test]
+    fn circular_mean_of_identical_angles_is_that_angle() {
+        let mean = circular_mean([0.4, 0.4, 0.4].into_iter());
+        assert!((mean - 0.4).abs() < 1e-6);
+    }
+
+    #[test]
+    fn circular_mean_handles_wraparound_without_cancelling_out() {
+        use std::f32::consts::PI;
+        // A naive arithmetic mean of angles just on either side of the +/-PI
+        // seam collapses to ~0; the circular mean should stay near the seam.
+        let mean = circular_mean([PI - 0.1, -(PI - 0.1)].into_iter());
+        assert!(mean.abs() > PI - 0.2);
+    }
+
+    #[test]
+    fn wrap_angle_leaves_small_deltas_unchanged() {
+        assert!((wrap_angle(0.2) - 0.2).abs() < 1e-6);
+        assert!((wrap_angle(-0.2) - (-0.2)).abs() < 1e-6);
+    }
+
+    #[test]
+    fn wrap_angle_wraps_a_near_full_rotation_to_a_small_delta() {
+        use std::f32::consts::PI;
+        // Going from just under +PI to just over -PI is a small step in
+        // reality, not a near-full rotation the other way around.
+        let delta = (-PI + 0.05) - (PI - 0.05);
+        assert!(wrap_angle(delta).abs() < 0.2);
+    }
+}
@@ -0,0 +1,86 @@
+//! Aggregating several [`MTDevice`]s into a single tagged touch stream.
+//!
+//! A consumer with a builtin trackpad, an external Magic Trackpad, and a
+//! Magic Mouse would otherwise have to juggle [`MTDevice::devices`] and a
+//! per-device closure by hand. [`MTDeviceGroup`] owns them together and
+//! dispatches a single callback, with a [`DeviceTag`] attached to every
+//! frame so the consumer can tell which device it came from.
+
+use crate::{DeviceType, MTDevice, MTTouch};
+use anyhow::Result;
+use std::sync::Arc;
+
+/// Identifies which device a frame delivered through [`MTDeviceGroup::listen`]
+/// came from.
+#[derive(Debug, Clone, Copy)]
+pub struct DeviceTag {
+    pub device_id: i32,
+    pub family_id: i32,
+    pub device_type: DeviceType,
+}
+
+/// Owns a fleet of [`MTDevice`]s and exposes them as one tagged touch stream.
+pub struct MTDeviceGroup {
+    devices: Vec<MTDevice>,
+}
+
+impl MTDeviceGroup {
+    /// Takes ownership of `devices` to be listened to together.
+    pub fn from_devices(devices: Vec<MTDevice>) -> Self {
+        Self { devices }
+    }
+
+    /// The tags of the devices in this group, in the same order they were
+    /// passed to [`Self::from_devices`].
+    pub fn tags(&self) -> Vec<DeviceTag> {
+        self.devices.iter().map(tag_for).collect()
+    }
+
+    /// Starts every device in the group and dispatches a single `callback`
+    /// for frames from any of them, tagged with the originating device.
+    ///
+    /// All-or-nothing: if any device fails to start, every device already
+    /// started by this call is stopped again before the error is returned,
+    /// so the group never ends up half-listening.
+    pub fn listen<F>(&mut self, callback: F) -> Result<()>
+    where
+        F: Fn(DeviceTag, &[MTTouch], i32, f64, i32) + Send + Sync + 'static,
+    {
+        let callback = Arc::new(callback);
+        let mut started: Vec<usize> = Vec::new();
+
+        for index in 0..self.devices.len() {
+            let device = &mut self.devices[index];
+            let tag = tag_for(device);
+            let callback = Arc::clone(&callback);
+
+            if let Err(err) = device.listen(move |_device, touches, fingers, timestamp, frame| {
+                callback(tag, touches, fingers, timestamp, frame);
+            }) {
+                for index in started {
+                    self.devices[index].stop();
+                }
+                return Err(err);
+            }
+
+            started.push(index);
+        }
+
+        Ok(())
+    }
+
+    /// Stops every device in the group.
+    pub fn stop(&mut self) {
+        for device in &mut self.devices {
+            device.stop();
+        }
+    }
+}
+
+fn tag_for(device: &MTDevice) -> DeviceTag {
+    DeviceTag {
+        device_id: device.device_id(),
+        family_id: device.family_id(),
+        device_type: device.device_type(),
+    }
+}
@@ -2,6 +2,12 @@ use anyhow::{bail, Result};
 use core_foundation::array::{CFArrayGetCount, CFArrayGetValueAtIndex, CFArrayRef};
 use std::{ffi::c_void, fmt::Debug, panic::catch_unwind};
 
+pub mod click;
+pub mod gestures;
+pub mod group;
+pub mod monitor;
+pub mod recording;
+
 #[link(name = "MultitouchSupport", kind = "framework")]
 extern "C" {
     fn MTDeviceCreateList() -> CFArrayRef;
@@ -224,6 +230,7 @@ impl Drop for MTDevice {
 
 /// Just a point (x, y)
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct MTPoint {
     pub x: f32,
@@ -232,6 +239,7 @@ pub struct MTPoint {
 
 /// A struct that contains the current touch position, and velocity
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct MTVector {
     /// current touch position
@@ -242,6 +250,7 @@ pub struct MTVector {
 
 /// The state of an individual touch on a Multitouch device / trackpad.
 #[derive(Debug, Clone, Copy)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub enum MTTouchState {
     NotTracking = 0,
@@ -264,6 +273,7 @@ pub enum MTTouchState {
 /// <https://chuck.cs.princeton.edu/release/files/examples/chuck-embed/core/util_hid.cpp> maybe useful
 /// <https://github.com/JitouchApp/Jitouch/blob/3b5018e4bc839426a6ce0917cea6df753d19da10/Application/Gesture.m#L2930>
 #[derive(Debug, Copy, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 #[repr(C)]
 pub struct MTTouch {
     /// The current frame number
@@ -334,3 +344,35 @@ extern "C" fn callback(
         Err(_) => -1,
     }
 }
+
+/// Test-only fixture shared by modules whose logic operates on [`MTTouch`]
+/// but doesn't need a real device (`recording`, `click`): a fully-populated,
+/// otherwise-neutral touch, meant to be overridden field-by-field with
+/// struct update syntax rather than copy-pasted.
+#[cfg(test)]
+pub(crate) fn sample_touch() -> MTTouch {
+    MTTouch {
+        frame: 0,
+        timestamp: 0.0,
+        identifier: 0,
+        state: MTTouchState::Touching,
+        finger_id: 0,
+        hand_id: 0,
+        normalized: MTVector {
+            pos: MTPoint { x: 0.0, y: 0.0 },
+            vel: MTPoint { x: 0.0, y: 0.0 },
+        },
+        z_total: 0.0,
+        unknown3: 0,
+        angle: 0.0,
+        major_axis: 0.0,
+        minor_axis: 0.0,
+        absolute: MTVector {
+            pos: MTPoint { x: 0.0, y: 0.0 },
+            vel: MTPoint { x: 0.0, y: 0.0 },
+        },
+        unknown4: 0,
+        unknown5: 0,
+        z_density: 0.0,
+    }
+}
@@ -0,0 +1,90 @@
+//! Hotplug detection and stable device re-identification.
+//!
+//! [`MTDevice::devices`] is a one-shot snapshot with no identity
+//! persistence: a long-running listener has no way to recover when an
+//! external Magic Trackpad sleeps and reconnects with a fresh
+//! [`MTDeviceRef`](crate::MTDeviceRef). [`MTDevice::from_device_id`] re-resolves
+//! a device by the stable id it reported earlier, and [`MTDeviceMonitor`]
+//! polls for devices appearing and disappearing, similar in spirit to
+//! winit's `DeviceId::into_raw()`/`from_raw()`.
+
+use crate::MTDevice;
+use std::{
+    collections::HashSet,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread,
+    time::Duration,
+};
+
+impl MTDevice {
+    /// Re-resolves a device by the [`Self::device_id`] it reported earlier,
+    /// by matching it against a fresh [`Self::devices`] snapshot. Returns
+    /// `None` if no currently connected device has that id, e.g. because it
+    /// was unplugged.
+    pub fn from_device_id(id: i32) -> Option<MTDevice> {
+        MTDevice::devices().into_iter().find(|d| d.device_id() == id)
+    }
+}
+
+/// A device appearing or disappearing, as delivered by [`MTDeviceMonitor`].
+pub enum DeviceEvent {
+    Added(MTDevice),
+    Removed(i32),
+}
+
+/// Polls for multitouch devices connecting and disconnecting and reports the
+/// change as a [`DeviceEvent`].
+pub struct MTDeviceMonitor {
+    stop: Arc<AtomicBool>,
+}
+
+impl MTDeviceMonitor {
+    /// Starts polling [`MTDevice::devices`] every `interval` on a background
+    /// thread, calling `callback` once per device that newly appears or
+    /// disappears compared to the previous poll.
+    pub fn start<F>(interval: Duration, callback: F) -> Self
+    where
+        F: Fn(DeviceEvent) + Send + 'static,
+    {
+        let stop = Arc::new(AtomicBool::new(false));
+        let stop_loop = Arc::clone(&stop);
+
+        thread::spawn(move || {
+            let mut known: HashSet<i32> = HashSet::new();
+
+            while !stop_loop.load(Ordering::SeqCst) {
+                let current = MTDevice::devices();
+                let current_ids: HashSet<i32> = current.iter().map(MTDevice::device_id).collect();
+
+                for id in known.difference(&current_ids) {
+                    callback(DeviceEvent::Removed(*id));
+                }
+
+                for device in current {
+                    if !known.contains(&device.device_id()) {
+                        callback(DeviceEvent::Added(device));
+                    }
+                }
+
+                known = current_ids;
+                thread::sleep(interval);
+            }
+        });
+
+        Self { stop }
+    }
+
+    /// Stops polling for device changes.
+    pub fn stop(&self) {
+        self.stop.store(true, Ordering::SeqCst);
+    }
+}
+
+impl Drop for MTDeviceMonitor {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
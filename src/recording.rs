@@ -0,0 +1,383 @@
+//! Record-and-replay support for multitouch frames.
+//!
+//! [`MTDevice::record`] wraps [`MTDevice::listen`] and mirrors every frame
+//! delivered to the user callback into an in-memory timeline, which can be
+//! flushed to disk and later driven back through a callback with
+//! [`MTDevice::replay`] using the original inter-frame timing. This makes it
+//! possible to exercise gesture code against a captured session without a
+//! physical trackpad attached.
+
+use crate::{MTDevice, MTDeviceRef, MTPoint, MTTouch, MTTouchState, MTVector};
+use anyhow::{bail, Context, Result};
+use std::{
+    fs::File,
+    io::{BufReader, BufWriter, Read, Write},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+/// A single recorded frame: the timestamp it arrived with, and the touches
+/// that were live in it.
+#[derive(Debug, Clone)]
+pub struct RecordedFrame {
+    pub timestamp: f64,
+    pub touches: Vec<MTTouch>,
+}
+
+/// A handle to an in-progress recording started by [`MTDevice::record`].
+///
+/// Dropping the handle does not stop the recording; call [`Self::stop`] to
+/// stop capturing frames and flush the timeline to disk.
+pub struct RecordingHandle {
+    frames: Arc<Mutex<Vec<RecordedFrame>>>,
+    active: Arc<AtomicBool>,
+    path: PathBuf,
+}
+
+impl RecordingHandle {
+    /// Writes every frame captured so far to the recording's file, without
+    /// stopping capture.
+    pub fn flush(&self) -> Result<()> {
+        let frames = self.frames.lock().unwrap();
+        write_timeline(&self.path, &frames)
+    }
+
+    /// Stops capturing new frames and flushes the timeline to disk.
+    pub fn stop(&self) -> Result<()> {
+        self.active.store(false, Ordering::SeqCst);
+        self.flush()
+    }
+}
+
+impl MTDevice {
+    /// Like [`Self::listen`], but also mirrors every delivered frame into an
+    /// in-memory timeline. Nothing is written to `path` until the returned
+    /// [`RecordingHandle`] is explicitly flushed or stopped, so a long
+    /// recording that never calls either keeps growing in memory and loses
+    /// everything captured so far if the process crashes first.
+    pub fn record<F>(&mut self, path: impl AsRef<Path>, inner_callback: F) -> Result<RecordingHandle>
+    where
+        F: Fn(MTDeviceRef, &[MTTouch], i32, f64, i32) + Send + Sync + 'static,
+    {
+        let frames: Arc<Mutex<Vec<RecordedFrame>>> = Arc::new(Mutex::new(Vec::new()));
+        let active = Arc::new(AtomicBool::new(true));
+
+        let handle = RecordingHandle {
+            frames: Arc::clone(&frames),
+            active: Arc::clone(&active),
+            path: path.as_ref().to_path_buf(),
+        };
+
+        self.listen(move |device, touches, fingers, timestamp, frame| {
+            if active.load(Ordering::SeqCst) {
+                frames.lock().unwrap().push(RecordedFrame {
+                    timestamp,
+                    touches: touches.to_vec(),
+                });
+            }
+            inner_callback(device, touches, fingers, timestamp, frame);
+        })?;
+
+        Ok(handle)
+    }
+
+    /// Replays a timeline previously captured with [`Self::record`], driving
+    /// `callback` with the original inter-frame timing (sleeping
+    /// `timestamp[n+1] - timestamp[n]` between frames).
+    ///
+    /// Since there is no real device behind a replay, `callback` is given a
+    /// null [`MTDeviceRef`].
+    pub fn replay<F>(path: impl AsRef<Path>, callback: F) -> Result<()>
+    where
+        F: Fn(MTDeviceRef, &[MTTouch], i32, f64, i32),
+    {
+        let frames = read_timeline(path.as_ref())?;
+
+        let mut prev_timestamp = None;
+        for (idx, frame) in frames.iter().enumerate() {
+            if let Some(prev) = prev_timestamp {
+                let dt = frame.timestamp - prev;
+                if dt > 0.0 {
+                    thread::sleep(Duration::from_secs_f64(dt));
+                }
+            }
+            prev_timestamp = Some(frame.timestamp);
+
+            callback(
+                std::ptr::null_mut(),
+                &frame.touches,
+                frame.touches.len() as i32,
+                frame.timestamp,
+                idx as i32,
+            );
+        }
+
+        Ok(())
+    }
+}
+
+fn write_timeline(path: &Path, frames: &[RecordedFrame]) -> Result<()> {
+    let file = File::create(path).with_context(|| format!("creating {}", path.display()))?;
+    let mut writer = BufWriter::new(file);
+
+    writer.write_all(&(frames.len() as u32).to_le_bytes())?;
+    for frame in frames {
+        write_frame(&mut writer, frame)?;
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+fn read_timeline(path: &Path) -> Result<Vec<RecordedFrame>> {
+    let file = File::open(path).with_context(|| format!("opening {}", path.display()))?;
+    let mut reader = BufReader::new(file);
+
+    let frame_count = read_u32(&mut reader)?;
+    let mut frames = Vec::with_capacity(frame_count as usize);
+    for _ in 0..frame_count {
+        frames.push(read_frame(&mut reader)?);
+    }
+    Ok(frames)
+}
+
+// Binary frame format: a length-prefixed list of frames, each a little-endian
+// `f64` timestamp followed by a length-prefixed list of `MTTouch`, each field
+// written out explicitly (rather than transmuting the `#[repr(C)]` struct)
+// so the format doesn't depend on platform struct layout.
+
+fn write_frame(w: &mut impl Write, frame: &RecordedFrame) -> Result<()> {
+    w.write_all(&frame.timestamp.to_le_bytes())?;
+    w.write_all(&(frame.touches.len() as u32).to_le_bytes())?;
+    for touch in &frame.touches {
+        write_touch(w, touch)?;
+    }
+    Ok(())
+}
+
+fn read_frame(r: &mut impl Read) -> Result<RecordedFrame> {
+    let timestamp = read_f64(r)?;
+    let touch_count = read_u32(r)?;
+    let mut touches = Vec::with_capacity(touch_count as usize);
+    for _ in 0..touch_count {
+        touches.push(read_touch(r)?);
+    }
+    Ok(RecordedFrame { timestamp, touches })
+}
+
+fn write_touch(w: &mut impl Write, t: &MTTouch) -> Result<()> {
+    w.write_all(&t.frame.to_le_bytes())?;
+    w.write_all(&t.timestamp.to_le_bytes())?;
+    w.write_all(&t.identifier.to_le_bytes())?;
+    w.write_all(&(t.state as i32).to_le_bytes())?;
+    w.write_all(&t.finger_id.to_le_bytes())?;
+    w.write_all(&t.hand_id.to_le_bytes())?;
+    write_vector(w, &t.normalized)?;
+    w.write_all(&t.z_total.to_le_bytes())?;
+    w.write_all(&t.unknown3.to_le_bytes())?;
+    w.write_all(&t.angle.to_le_bytes())?;
+    w.write_all(&t.major_axis.to_le_bytes())?;
+    w.write_all(&t.minor_axis.to_le_bytes())?;
+    write_vector(w, &t.absolute)?;
+    w.write_all(&t.unknown4.to_le_bytes())?;
+    w.write_all(&t.unknown5.to_le_bytes())?;
+    w.write_all(&t.z_density.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_touch(r: &mut impl Read) -> Result<MTTouch> {
+    Ok(MTTouch {
+        frame: read_i32(r)?,
+        timestamp: read_f64(r)?,
+        identifier: read_i32(r)?,
+        state: touch_state_from_i32(read_i32(r)?)?,
+        finger_id: read_i32(r)?,
+        hand_id: read_i32(r)?,
+        normalized: read_vector(r)?,
+        z_total: read_f32(r)?,
+        unknown3: read_i32(r)?,
+        angle: read_f32(r)?,
+        major_axis: read_f32(r)?,
+        minor_axis: read_f32(r)?,
+        absolute: read_vector(r)?,
+        unknown4: read_i32(r)?,
+        unknown5: read_i32(r)?,
+        z_density: read_f32(r)?,
+    })
+}
+
+fn write_vector(w: &mut impl Write, v: &MTVector) -> Result<()> {
+    write_point(w, &v.pos)?;
+    write_point(w, &v.vel)?;
+    Ok(())
+}
+
+fn read_vector(r: &mut impl Read) -> Result<MTVector> {
+    Ok(MTVector {
+        pos: read_point(r)?,
+        vel: read_point(r)?,
+    })
+}
+
+fn write_point(w: &mut impl Write, p: &MTPoint) -> Result<()> {
+    w.write_all(&p.x.to_le_bytes())?;
+    w.write_all(&p.y.to_le_bytes())?;
+    Ok(())
+}
+
+fn read_point(r: &mut impl Read) -> Result<MTPoint> {
+    Ok(MTPoint {
+        x: read_f32(r)?,
+        y: read_f32(r)?,
+    })
+}
+
+fn touch_state_from_i32(value: i32) -> Result<MTTouchState> {
+    Ok(match value {
+        0 => MTTouchState::NotTracking,
+        1 => MTTouchState::StartInRange,
+        2 => MTTouchState::HoverInRange,
+        3 => MTTouchState::MakeTouch,
+        4 => MTTouchState::Touching,
+        5 => MTTouchState::BreakTouch,
+        6 => MTTouchState::LingerInRange,
+        7 => MTTouchState::OutOfRange,
+        other => bail!("invalid MTTouchState discriminant: {other}"),
+    })
+}
+
+fn read_u32(r: &mut impl Read) -> Result<u32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(u32::from_le_bytes(buf))
+}
+
+fn read_i32(r: &mut impl Read) -> Result<i32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(i32::from_le_bytes(buf))
+}
+
+fn read_f32(r: &mut impl Read) -> Result<f32> {
+    let mut buf = [0u8; 4];
+    r.read_exact(&mut buf)?;
+    Ok(f32::from_le_bytes(buf))
+}
+
+fn read_f64(r: &mut impl Read) -> Result<f64> {
+    let mut buf = [0u8; 8];
+    r.read_exact(&mut buf)?;
+    Ok(f64::from_le_bytes(buf))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sample_touch(identifier: i32, state: MTTouchState) -> MTTouch {
+        MTTouch {
+            frame: 7,
+            timestamp: 1.5,
+            identifier,
+            state,
+            finger_id: 1,
+            normalized: MTVector {
+                pos: MTPoint { x: 0.25, y: 0.75 },
+                vel: MTPoint { x: 0.1, y: -0.2 },
+            },
+            z_total: 12.5,
+            angle: 0.3,
+            major_axis: 5.0,
+            minor_axis: 2.0,
+            absolute: MTVector {
+                pos: MTPoint { x: 100.0, y: 200.0 },
+                vel: MTPoint { x: 1.0, y: 2.0 },
+            },
+            z_density: 3.5,
+            ..crate::sample_touch()
+        }
+    }
+
+    fn assert_touches_eq(a: &MTTouch, b: &MTTouch) {
+        assert_eq!(a.frame, b.frame);
+        assert_eq!(a.timestamp, b.timestamp);
+        assert_eq!(a.identifier, b.identifier);
+        assert_eq!(a.state as i32, b.state as i32);
+        assert_eq!(a.finger_id, b.finger_id);
+        assert_eq!(a.hand_id, b.hand_id);
+        assert_eq!(a.normalized.pos.x, b.normalized.pos.x);
+        assert_eq!(a.normalized.pos.y, b.normalized.pos.y);
+        assert_eq!(a.normalized.vel.x, b.normalized.vel.x);
+        assert_eq!(a.normalized.vel.y, b.normalized.vel.y);
+        assert_eq!(a.z_total, b.z_total);
+        assert_eq!(a.angle, b.angle);
+        assert_eq!(a.major_axis, b.major_axis);
+        assert_eq!(a.minor_axis, b.minor_axis);
+        assert_eq!(a.absolute.pos.x, b.absolute.pos.x);
+        assert_eq!(a.absolute.pos.y, b.absolute.pos.y);
+        assert_eq!(a.absolute.vel.x, b.absolute.vel.x);
+        assert_eq!(a.absolute.vel.y, b.absolute.vel.y);
+        assert_eq!(a.z_density, b.z_density);
+    }
+
+    #[test]
+    fn frame_round_trips_through_write_and_read() {
+        let frame = RecordedFrame {
+            timestamp: 42.125,
+            touches: vec![
+                sample_touch(1, MTTouchState::Touching),
+                sample_touch(2, MTTouchState::MakeTouch),
+            ],
+        };
+
+        let mut buf = Vec::new();
+        write_frame(&mut buf, &frame).unwrap();
+        let decoded = read_frame(&mut buf.as_slice()).unwrap();
+
+        assert_eq!(decoded.timestamp, frame.timestamp);
+        assert_eq!(decoded.touches.len(), frame.touches.len());
+        for (expected, actual) in frame.touches.iter().zip(decoded.touches.iter()) {
+            assert_touches_eq(expected, actual);
+        }
+    }
+
+    #[test]
+    fn timeline_round_trips_through_disk() {
+        let frames = vec![
+            RecordedFrame {
+                timestamp: 0.0,
+                touches: vec![sample_touch(1, MTTouchState::Touching)],
+            },
+            RecordedFrame {
+                timestamp: 0.016,
+                touches: Vec::new(),
+            },
+        ];
+
+        let path = std::env::temp_dir().join(format!(
+            "trackpad-rs-timeline-round-trip-test-{}.bin",
+            std::process::id()
+        ));
+        write_timeline(&path, &frames).unwrap();
+        let decoded = read_timeline(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+
+        assert_eq!(decoded.len(), frames.len());
+        for (expected, actual) in frames.iter().zip(decoded.iter()) {
+            assert_eq!(expected.timestamp, actual.timestamp);
+            assert_eq!(expected.touches.len(), actual.touches.len());
+            for (e, a) in expected.touches.iter().zip(actual.touches.iter()) {
+                assert_touches_eq(e, a);
+            }
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_touch_state_discriminant() {
+        assert!(touch_state_from_i32(42).is_err());
+    }
+}